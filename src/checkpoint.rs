@@ -1,11 +1,34 @@
-use crate::types::{Checkpoint, Session};
+use crate::chunk::ChunkStore;
+use crate::diff;
+use crate::git;
+use crate::types::{
+    CheckpointDiff, CheckpointMode, CheckpointSort, Checkpoint, DiffType, FileDiff,
+    FileManifestEntry, GuardianConfig, PruneScope, Session,
+};
 use anyhow::{Context, Result};
 use chrono::Utc;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
 use serde_json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
+/// Directory (relative to `storage_path`) holding per-checkpoint file manifests.
+const MANIFESTS_DIR: &str = "manifests";
+/// Name of the consolidated state file (replaces the legacy `checkpoints.json` +
+/// `sessions.json` pair).
+const STATE_FILE: &str = "state.json";
+
+/// On-disk layout for the consolidated state file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedState {
+    checkpoints: Vec<Checkpoint>,
+    sessions: Vec<Session>,
+}
+
 /// Manages checkpoints and sessions
 pub struct CheckpointManager {
     /// Path to the storage directory
@@ -16,51 +39,168 @@ pub struct CheckpointManager {
     sessions: Vec<Session>,
     /// Current session ID
     current_session_id: Option<String>,
+    /// Content-addressable store backing checkpoint file content
+    chunk_store: ChunkStore,
+    /// Checkpoint cadence policy
+    config: GuardianConfig,
+    /// File changes observed since the last automatic checkpoint
+    pending_ops: u32,
+    /// Timestamp (ms) of the last automatic checkpoint, if any; seeded on load from the
+    /// newest stored checkpoint, automatic or not
+    last_auto_checkpoint_at: Option<i64>,
 }
 
 impl CheckpointManager {
     /// Create a new checkpoint manager
     pub fn new(storage_path: &Path) -> Result<Self> {
         fs::create_dir_all(storage_path)?;
+        let chunk_store = ChunkStore::new(storage_path)?;
         let mut manager = Self {
             storage_path: storage_path.to_path_buf(),
             checkpoints: Vec::new(),
             sessions: Vec::new(),
             current_session_id: None,
+            chunk_store,
+            config: GuardianConfig::default(),
+            pending_ops: 0,
+            last_auto_checkpoint_at: None,
         };
         manager.load_from_disk()?;
+        manager.last_auto_checkpoint_at = manager.checkpoints.iter().map(|c| c.timestamp).max();
         Ok(manager)
     }
 
-    /// Load checkpoints and sessions from disk
+    /// Replace the cadence/storage policy used for automatic checkpoints.
+    ///
+    /// `new` loads with the default policy, so `config.ignore_before` is applied to the
+    /// loaded checkpoints here as well.
+    pub fn set_config(&mut self, config: GuardianConfig) {
+        self.config = config;
+        if let Some(horizon) = self.config.ignore_before {
+            self.checkpoints.retain(|c| c.timestamp >= horizon);
+        }
+    }
+
+    /// The cadence/storage policy currently in effect.
+    pub fn config(&self) -> &GuardianConfig {
+        &self.config
+    }
+
+    /// Record that a file change was observed, counting towards the debounce threshold
+    /// consulted by `maybe_auto_checkpoint`.
+    pub fn record_change(&mut self) {
+        self.pending_ops += 1;
+    }
+
+    /// Possibly create an automatic checkpoint, subject to the configured `CheckpointMode`
+    /// and both debounce guards (minimum elapsed time, minimum accumulated changes).
+    /// Returns `None` if no checkpoint was warranted.
+    pub fn maybe_auto_checkpoint(&mut self, worktree_path: &Path) -> Result<Option<Checkpoint>> {
+        let op_threshold = match self.config.checkpoint_mode {
+            CheckpointMode::Never => return Ok(None),
+            CheckpointMode::Every(n) => n.max(self.config.min_checkpoint_ops),
+            CheckpointMode::Always => self.config.min_checkpoint_ops,
+        };
+
+        if self.pending_ops < op_threshold.max(1) {
+            return Ok(None);
+        }
+
+        if let Some(last) = self.last_auto_checkpoint_at {
+            let elapsed_secs = (Utc::now().timestamp_millis() - last) / 1000;
+            if elapsed_secs < self.config.min_checkpoint_interval_secs as i64 {
+                return Ok(None);
+            }
+        }
+
+        let name = format!("Auto Checkpoint {}", self.checkpoints.len() + 1);
+        let checkpoint = self.create_checkpoint(name, worktree_path)?;
+        self.pending_ops = 0;
+        self.last_auto_checkpoint_at = Some(checkpoint.timestamp);
+        Ok(Some(checkpoint))
+    }
+
+    /// Load checkpoints and sessions from the consolidated state file, transparently
+    /// migrating from the legacy `checkpoints.json` + `sessions.json` layout if that's
+    /// all that's present, and dropping any checkpoint older than `config.ignore_before`.
     fn load_from_disk(&mut self) -> Result<()> {
+        let state_path = self.state_path();
+        if state_path.exists() {
+            let content = fs::read_to_string(&state_path)?;
+            let state: PersistedState = serde_json::from_str(&content)?;
+            self.checkpoints = state.checkpoints;
+            self.sessions = state.sessions;
+        } else {
+            self.load_legacy_state()?;
+        }
+
+        if let Some(horizon) = self.config.ignore_before {
+            self.checkpoints.retain(|c| c.timestamp >= horizon);
+        }
+
+        Ok(())
+    }
+
+    /// Read the legacy two-file layout, if present, and immediately rewrite it as the
+    /// consolidated state file so future loads take the fast path.
+    fn load_legacy_state(&mut self) -> Result<()> {
         let checkpoints_file = self.storage_path.join("checkpoints.json");
         let sessions_file = self.storage_path.join("sessions.json");
+        let mut migrated = false;
 
         if checkpoints_file.exists() {
             let content = fs::read_to_string(&checkpoints_file)?;
             self.checkpoints = serde_json::from_str(&content)?;
+            migrated = true;
         }
-
         if sessions_file.exists() {
             let content = fs::read_to_string(&sessions_file)?;
             self.sessions = serde_json::from_str(&content)?;
+            migrated = true;
+        }
+
+        if migrated {
+            self.save_to_disk()?;
+            let _ = fs::remove_file(&checkpoints_file);
+            let _ = fs::remove_file(&sessions_file);
+            log::info!("Migrated legacy checkpoint/session files to {}", STATE_FILE);
         }
 
         Ok(())
     }
 
-    /// Save checkpoints and sessions to disk
-    fn save_to_disk(&self) -> Result<()> {
-        let checkpoints_file = self.storage_path.join("checkpoints.json");
-        let sessions_file = self.storage_path.join("sessions.json");
-
-        let checkpoints_json = serde_json::to_string_pretty(&self.checkpoints, Default::default())?;
-        let sessions_json = serde_json::to_string_pretty(&self.sessions, Default::default())?;
+    /// Path to the consolidated state file.
+    fn state_path(&self) -> PathBuf {
+        self.storage_path.join(STATE_FILE)
+    }
 
-        fs::write(&checkpoints_file, checkpoints_json)?;
-        fs::write(&sessions_file, sessions_json)?;
+    /// Save checkpoints and sessions to the consolidated state file, crash-safely.
+    fn save_to_disk(&self) -> Result<()> {
+        let state = PersistedState {
+            checkpoints: self.checkpoints.clone(),
+            sessions: self.sessions.clone(),
+        };
+        let json = serde_json::to_string_pretty(&state)?;
+        Self::write_atomic(&self.state_path(), json.as_bytes())
+    }
 
+    /// Write `data` to `path` crash-safely: write to a temp file in the same directory,
+    /// fsync it, then rename over the real path so readers never observe a partial write.
+    fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+        let dir = path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("state path has no parent directory: {}", path.display()))?;
+        let tmp_path = dir.join(format!(
+            ".{}.tmp",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp_path, path)?;
         Ok(())
     }
 
@@ -105,16 +245,35 @@ impl CheckpointManager {
     ) -> Result<Checkpoint> {
         let session_id = self.current_session_id
             .clone()
-            .unwrap_or_else(|| Self::generate_id());
+            .unwrap_or_else(Self::generate_id);
 
         let timestamp = Utc::now().timestamp_millis();
         let id = Self::generate_id();
 
-        // Track files
-        let file_count = self.track_files(worktree_path)?;
+        // Track files and capture their current content into the chunk store
+        let files = self.track_files(worktree_path)?;
+        let manifest = self.capture_manifest(worktree_path, &files)?;
+        let file_count = manifest.len();
 
-        // Try to get git commit hash
-        let commit_hash = self.get_git_commit(worktree_path)?;
+        let git_status = git::status(worktree_path);
+        if let Some(status) = &git_status {
+            log::debug!("git status at checkpoint: branch={:?} dirty={}", status.branch, status.dirty);
+        }
+        // Informational only: this is HEAD at checkpoint time, which may predate
+        // uncommitted changes the checkpoint itself captured. Never used for restore.
+        let commit_hash = git_status.as_ref().and_then(|s| s.commit_hash.clone());
+        let branch = git_status.as_ref().and_then(|s| s.branch.clone());
+
+        // When the repo supports it, prefer a git-backed snapshot: a dangling commit
+        // object holding the checkpoint's own content, so rollback can restore straight
+        // from the repository instead of only the chunk-store manifest. Kept in its own
+        // field so rollback never mistakes the informational `commit_hash` above for a
+        // restorable tree.
+        let git_snapshot_commit = if self.config.git_snapshot_enabled {
+            git::snapshot_commit(worktree_path, &files).ok()
+        } else {
+            None
+        };
 
         let checkpoint = Checkpoint {
             id: id.clone(),
@@ -123,10 +282,14 @@ impl CheckpointManager {
             commit_hash,
             session_id,
             file_count,
+            branch,
+            git_snapshot_commit,
         };
 
+        self.save_manifest(&id, &manifest)?;
         self.checkpoints.push(checkpoint.clone());
         self.save_to_disk()?;
+        self.enforce_checkpoint_cap(&checkpoint.session_id)?;
 
         log::info!("Created checkpoint: {} with {} files", id, file_count);
         Ok(checkpoint)
@@ -138,21 +301,131 @@ impl CheckpointManager {
         self.create_checkpoint(name, worktree_path)
     }
 
-    /// Rollback to a checkpoint
+    /// Rollback to a checkpoint, restoring every tracked file to its captured state.
+    ///
+    /// Files present in the checkpoint are recreated (if deleted) or overwritten (if
+    /// modified); files that did not exist at checkpoint time but exist now are removed.
     pub fn rollback(&self, checkpoint_id: &str, worktree_path: &Path) -> Result<()> {
-        let checkpoint = self.checkpoints
+        let checkpoint = self
+            .checkpoints
             .iter()
             .find(|c| c.id == checkpoint_id)
             .ok_or_else(|| anyhow::anyhow!("Checkpoint not found: {}", checkpoint_id))?;
 
-        log::info!("Rolling back to checkpoint: {}", checkpoint_id);
+        // Prefer restoring straight from the git-backed snapshot commit, if this
+        // checkpoint has one; fall back to the chunk-store manifest otherwise (e.g. no
+        // git dir, or the snapshot strategy wasn't enabled when the checkpoint was made).
+        // Deliberately keyed off `git_snapshot_commit`, not the informational `commit_hash`:
+        // the latter is HEAD at checkpoint time and may not hold the checkpoint's own content.
+        let restored: HashSet<PathBuf> = match checkpoint
+            .git_snapshot_commit
+            .as_deref()
+            .and_then(|hash| git::restore_commit(worktree_path, hash).ok())
+        {
+            Some(paths) => paths.into_iter().collect(),
+            None => self.restore_from_manifest(checkpoint_id, worktree_path)?,
+        };
 
-        // TODO: Implement actual rollback logic
-        // This would restore files from the checkpoint
+        // Anything tracked now that wasn't part of the checkpoint was added afterwards.
+        for rel_path in self.track_files(worktree_path)? {
+            if !restored.contains(&rel_path) {
+                let _ = fs::remove_file(worktree_path.join(&rel_path));
+            }
+        }
 
+        log::info!(
+            "Rolled back to checkpoint: {} ({} files restored)",
+            checkpoint_id,
+            restored.len()
+        );
         Ok(())
     }
 
+    /// Restore every file in a checkpoint's chunk-store manifest onto disk, returning
+    /// the set of paths written.
+    fn restore_from_manifest(&self, checkpoint_id: &str, worktree_path: &Path) -> Result<HashSet<PathBuf>> {
+        let manifest = self.load_manifest(checkpoint_id)?;
+        let mut restored = HashSet::new();
+
+        for entry in &manifest {
+            let rel_path = PathBuf::from(&entry.path);
+            let full_path = worktree_path.join(&rel_path);
+            let content = self.chunk_store.get(&entry.chunk_ids)?;
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&full_path, content)?;
+            restored.insert(rel_path);
+        }
+        Ok(restored)
+    }
+
+    /// Diff a checkpoint's captured content against the current state of the worktree.
+    pub fn diff_checkpoint(&self, checkpoint_id: &str, worktree_path: &Path) -> Result<CheckpointDiff> {
+        self.checkpoints
+            .iter()
+            .find(|c| c.id == checkpoint_id)
+            .ok_or_else(|| anyhow::anyhow!("Checkpoint not found: {}", checkpoint_id))?;
+
+        let manifest = self.load_manifest(checkpoint_id)?;
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut files = Vec::new();
+
+        for entry in &manifest {
+            let rel_path = PathBuf::from(&entry.path);
+            seen.insert(rel_path.clone());
+            let full_path = worktree_path.join(&rel_path);
+
+            let old_bytes = self.chunk_store.get(&entry.chunk_ids)?;
+            let old_content = String::from_utf8_lossy(&old_bytes).to_string();
+
+            if !full_path.exists() {
+                files.push(FileDiff {
+                    path: entry.path.clone(),
+                    diff_type: DiffType::Deleted,
+                    hunks: diff::unified_diff(&old_content, ""),
+                });
+                continue;
+            }
+
+            let new_bytes = fs::read(&full_path).unwrap_or_default();
+            let new_content = String::from_utf8_lossy(&new_bytes).to_string();
+            if new_content == old_content {
+                files.push(FileDiff {
+                    path: entry.path.clone(),
+                    diff_type: DiffType::Unchanged,
+                    hunks: Vec::new(),
+                });
+            } else {
+                files.push(FileDiff {
+                    path: entry.path.clone(),
+                    diff_type: DiffType::Modified,
+                    hunks: diff::unified_diff(&old_content, &new_content),
+                });
+            }
+        }
+
+        // Anything tracked now that wasn't part of the checkpoint was added afterwards.
+        for rel_path in self.track_files(worktree_path)? {
+            if seen.contains(&rel_path) {
+                continue;
+            }
+            let full_path = worktree_path.join(&rel_path);
+            let new_bytes = fs::read(&full_path).unwrap_or_default();
+            let new_content = String::from_utf8_lossy(&new_bytes).to_string();
+            files.push(FileDiff {
+                path: rel_path.to_string_lossy().to_string(),
+                diff_type: DiffType::Added,
+                hunks: diff::unified_diff("", &new_content),
+            });
+        }
+
+        Ok(CheckpointDiff {
+            checkpoint_id: checkpoint_id.to_string(),
+            files,
+        })
+    }
+
     /// Delete a checkpoint
     pub fn delete_checkpoint(&mut self, checkpoint_id: &str) -> Result<()> {
         let index = self.checkpoints
@@ -162,18 +435,113 @@ impl CheckpointManager {
 
         self.checkpoints.remove(index);
         self.save_to_disk()?;
+        let _ = fs::remove_file(self.manifest_path(checkpoint_id));
+
+        let (removed, freed_bytes) = self.gc()?;
+        log::info!(
+            "Deleted checkpoint: {} (gc freed {} chunks, {} bytes)",
+            checkpoint_id,
+            removed,
+            freed_bytes
+        );
+        Ok(())
+    }
+
+    /// Delete chunk files no longer referenced by any remaining checkpoint.
+    pub fn gc(&self) -> Result<(usize, u64)> {
+        let mut live_ids = HashSet::new();
+        for checkpoint in &self.checkpoints {
+            if let Ok(manifest) = self.load_manifest(&checkpoint.id) {
+                live_ids.extend(manifest.into_iter().flat_map(|entry| entry.chunk_ids));
+            }
+        }
+        self.chunk_store.gc(&live_ids)
+    }
 
-        log::info!("Deleted checkpoint: {}", checkpoint_id);
+    /// Total size (bytes) of a checkpoint's captured file content.
+    fn checkpoint_size(&self, checkpoint_id: &str) -> Result<u64> {
+        let manifest = self.load_manifest(checkpoint_id)?;
+        let mut total = 0u64;
+        for entry in &manifest {
+            total += self.chunk_store.size(&entry.chunk_ids)?;
+        }
+        Ok(total)
+    }
+
+    /// Evict the oldest checkpoints in `session_id` beyond `config.max_checkpoints_per_session`.
+    fn enforce_checkpoint_cap(&mut self, session_id: &str) -> Result<()> {
+        let cap = self.config.max_checkpoints_per_session as usize;
+        let count = self.checkpoints.iter().filter(|c| c.session_id == session_id).count();
+        if count <= cap {
+            return Ok(());
+        }
+
+        self.prune_checkpoints(
+            Some(session_id),
+            PruneScope::Group {
+                sort: CheckpointSort::Oldest,
+                invert: false,
+                n: (count - cap) as u32,
+            },
+        )?;
         Ok(())
     }
 
-    /// List all checkpoints
-    pub fn list_checkpoints(&self, session_id: Option<&str>) -> Vec<&Checkpoint> {
-        if let Some(id) = session_id {
-            self.checkpoints.iter().filter(|c| c.session_id == id).collect()
-        } else {
-            self.checkpoints.iter().collect()
+    /// Resolve `scope` against the checkpoints in `session_id` (or all sessions if
+    /// `None`), returning the ids selected for pruning in eviction order.
+    fn select_prune_candidates(&self, session_id: Option<&str>, scope: &PruneScope) -> Vec<String> {
+        let mut candidates: Vec<Checkpoint> =
+            self.list_checkpoints(session_id, None).into_iter().cloned().collect();
+
+        let (sort, invert, n) = match scope {
+            PruneScope::All => return candidates.into_iter().map(|c| c.id).collect(),
+            PruneScope::Group { sort, invert, n } => (sort, *invert, *n as usize),
+        };
+
+        match sort {
+            CheckpointSort::Oldest => candidates.sort_by_key(|c| c.timestamp),
+            CheckpointSort::Alpha => candidates.sort_by(|a, b| a.name.cmp(&b.name)),
+            CheckpointSort::Largest => {
+                let sizes: HashMap<String, u64> = candidates
+                    .iter()
+                    .map(|c| (c.id.clone(), self.checkpoint_size(&c.id).unwrap_or(0)))
+                    .collect();
+                candidates.sort_by_key(|c| std::cmp::Reverse(sizes[&c.id]));
+            }
         }
+
+        if invert {
+            candidates.reverse();
+        }
+
+        candidates.into_iter().take(n).map(|c| c.id).collect()
+    }
+
+    /// Delete every checkpoint selected by `scope`, then garbage-collect any chunks
+    /// left unreferenced. Returns the number of checkpoints removed and the bytes freed.
+    pub fn prune_checkpoints(&mut self, session_id: Option<&str>, scope: PruneScope) -> Result<(usize, u64)> {
+        let victims = self.select_prune_candidates(session_id, &scope);
+
+        for id in &victims {
+            if let Some(index) = self.checkpoints.iter().position(|c| &c.id == id) {
+                self.checkpoints.remove(index);
+                let _ = fs::remove_file(self.manifest_path(id));
+            }
+        }
+        self.save_to_disk()?;
+
+        let (_, freed_bytes) = self.gc()?;
+        log::info!("Pruned {} checkpoint(s), freed {} bytes", victims.len(), freed_bytes);
+        Ok((victims.len(), freed_bytes))
+    }
+
+    /// List all checkpoints
+    pub fn list_checkpoints(&self, session_id: Option<&str>, branch: Option<&str>) -> Vec<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .filter(|c| session_id.is_none_or(|id| c.session_id == id))
+            .filter(|c| branch.is_none_or(|b| c.branch.as_deref() == Some(b)))
+            .collect()
     }
 
     /// List all sessions
@@ -181,38 +549,93 @@ impl CheckpointManager {
         &self.sessions
     }
 
-    /// Track files in the worktree
-    fn track_files(&self, worktree_path: &Path) -> Result<usize> {
-        let mut file_count = 0;
-        if let Ok(entries) = fs::read_dir(worktree_path) {
-            for entry in entries.flatten() {
-                if entry.path().is_file() {
-                    file_count += 1;
-                }
+    /// Track files in the worktree, returning their paths relative to `worktree_path`.
+    fn track_files(&self, worktree_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut builder = WalkBuilder::new(worktree_path);
+        builder
+            .hidden(false)
+            .git_ignore(true)
+            .git_global(true)
+            .git_exclude(true)
+            .parents(true)
+            // Honor .gitignore/.ignore even when `worktree_path` itself isn't a git
+            // repo (git_ignore/git_global/git_exclude are otherwise silently skipped).
+            .require_git(false);
+
+        if !self.config.extra_ignore_patterns.is_empty() {
+            let mut overrides = OverrideBuilder::new(worktree_path);
+            for pattern in &self.config.extra_ignore_patterns {
+                overrides.add(&format!("!{}", pattern))?;
             }
+            builder.overrides(overrides.build()?);
         }
-        Ok(file_count)
+
+        let mut files = Vec::new();
+        for entry in builder.build() {
+            let entry = entry?;
+            if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                continue;
+            }
+            let path = entry.path();
+            if path.components().any(|c| c.as_os_str() == ".git") {
+                continue;
+            }
+            if let Ok(rel_path) = path.strip_prefix(worktree_path) {
+                files.push(rel_path.to_path_buf());
+            }
+        }
+        Ok(files)
     }
 
-    /// Get current git commit hash
-    fn get_git_commit(&self, worktree_path: &Path) -> Result<Option<String>> {
-        let git_dir = worktree_path.join(".git");
-        if !git_dir.exists() {
-            return Ok(None);
+    /// Capture the current content of every tracked file into the chunk store,
+    /// returning each file's manifest entry.
+    ///
+    /// Files are captured best-effort: the worktree can be actively edited by an AI
+    /// while this runs, so a file vanishing between `track_files`'s walk and this read
+    /// (deleted, renamed) is expected, not exceptional. Skip that one entry rather than
+    /// failing the whole checkpoint.
+    fn capture_manifest(&self, worktree_path: &Path, files: &[PathBuf]) -> Result<Vec<FileManifestEntry>> {
+        let mut entries = Vec::with_capacity(files.len());
+        for rel_path in files {
+            let full_path = worktree_path.join(rel_path);
+            let content = match fs::read(&full_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    log::warn!(
+                        "Skipping {} in checkpoint manifest: {}",
+                        rel_path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            let chunk_ids = self.chunk_store.put(&content)?;
+            entries.push(FileManifestEntry {
+                path: rel_path.to_string_lossy().to_string(),
+                chunk_ids,
+            });
         }
+        Ok(entries)
+    }
 
-        // Use git to get the current commit
-        let output = std::process::Command::new("git")
-            .args(["rev-parse", "HEAD"])
-            .current_dir(worktree_path)
-            .output()?;
+    /// Path to the stored file manifest for a checkpoint.
+    fn manifest_path(&self, checkpoint_id: &str) -> PathBuf {
+        self.storage_path.join(MANIFESTS_DIR).join(format!("{}.json", checkpoint_id))
+    }
 
-        if output.status.success() {
-            let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            Ok(Some(hash))
-        } else {
-            Ok(None)
-        }
+    /// Persist a checkpoint's file manifest to disk.
+    fn save_manifest(&self, checkpoint_id: &str, manifest: &[FileManifestEntry]) -> Result<()> {
+        fs::create_dir_all(self.storage_path.join(MANIFESTS_DIR))?;
+        let json = serde_json::to_string_pretty(manifest)?;
+        fs::write(self.manifest_path(checkpoint_id), json)?;
+        Ok(())
+    }
+
+    /// Load a checkpoint's file manifest from disk.
+    fn load_manifest(&self, checkpoint_id: &str) -> Result<Vec<FileManifestEntry>> {
+        let content = fs::read_to_string(self.manifest_path(checkpoint_id))
+            .with_context(|| format!("no file manifest stored for checkpoint {}", checkpoint_id))?;
+        Ok(serde_json::from_str(&content)?)
     }
 
     /// Generate a unique ID
@@ -224,3 +647,136 @@ impl CheckpointManager {
         format!("cp_{}", duration.as_millis())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// A scratch directory under the system temp dir, removed when the guard drops.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(label: &str) -> Self {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let path = std::env::temp_dir().join(format!("vibe-guardian-test-{}-{}", label, nanos));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn rollback_restores_modified_deleted_and_added_files() {
+        let worktree = TempDir::new("worktree");
+        let storage = TempDir::new("storage");
+
+        fs::write(worktree.path().join("keep.txt"), b"original content").unwrap();
+        fs::write(
+            worktree.path().join("remove_me.txt"),
+            b"will be deleted after checkpoint",
+        )
+        .unwrap();
+
+        let mut manager = CheckpointManager::new(storage.path()).unwrap();
+        let checkpoint = manager
+            .create_checkpoint("cp1".to_string(), worktree.path())
+            .unwrap();
+        assert_eq!(checkpoint.file_count, 2);
+
+        // Modify an existing file, delete another, and add a brand new one.
+        fs::write(worktree.path().join("keep.txt"), b"modified content").unwrap();
+        fs::remove_file(worktree.path().join("remove_me.txt")).unwrap();
+        fs::write(worktree.path().join("new_file.txt"), b"added after checkpoint").unwrap();
+
+        manager.rollback(&checkpoint.id, worktree.path()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(worktree.path().join("keep.txt")).unwrap(),
+            "original content"
+        );
+        assert_eq!(
+            fs::read_to_string(worktree.path().join("remove_me.txt")).unwrap(),
+            "will be deleted after checkpoint"
+        );
+        assert!(!worktree.path().join("new_file.txt").exists());
+    }
+
+    #[test]
+    fn save_to_disk_tolerates_a_stale_tmp_file_left_by_a_prior_crash() {
+        let storage = TempDir::new("atomic");
+        let mut manager = CheckpointManager::new(storage.path()).unwrap();
+        manager.start_session(Some("s1".to_string())).unwrap();
+
+        // Simulate a crash mid-write: a leftover temp file from write_atomic should
+        // never be mistaken for the real state file on the next load.
+        let tmp_path = storage.path().join(format!(".{}.tmp", STATE_FILE));
+        fs::write(&tmp_path, b"not valid json, pretend a crash happened here").unwrap();
+
+        // Saving again must overwrite the stale temp file and still rename into place.
+        manager.end_session().unwrap();
+        assert!(!tmp_path.exists());
+
+        let reloaded = CheckpointManager::new(storage.path()).unwrap();
+        assert_eq!(reloaded.list_sessions().len(), 1);
+        assert_eq!(reloaded.list_sessions()[0].name, "s1");
+
+        // The real state file holds complete, valid JSON, not a partial write.
+        let content = fs::read_to_string(storage.path().join(STATE_FILE)).unwrap();
+        let _: serde_json::Value = serde_json::from_str(&content).unwrap();
+    }
+
+    #[test]
+    fn load_migrates_legacy_checkpoints_and_sessions_files() {
+        let storage = TempDir::new("legacy");
+
+        let legacy_checkpoints = vec![Checkpoint {
+            id: "cp_legacy".to_string(),
+            name: "Legacy".to_string(),
+            timestamp: 1,
+            commit_hash: None,
+            session_id: "s_legacy".to_string(),
+            file_count: 0,
+            branch: None,
+            git_snapshot_commit: None,
+        }];
+        fs::write(
+            storage.path().join("checkpoints.json"),
+            serde_json::to_string(&legacy_checkpoints).unwrap(),
+        )
+        .unwrap();
+
+        let legacy_sessions = vec![Session {
+            id: "s_legacy".to_string(),
+            name: "Legacy Session".to_string(),
+            start_time: 1,
+            end_time: None,
+        }];
+        fs::write(
+            storage.path().join("sessions.json"),
+            serde_json::to_string(&legacy_sessions).unwrap(),
+        )
+        .unwrap();
+
+        let manager = CheckpointManager::new(storage.path()).unwrap();
+        assert_eq!(manager.list_checkpoints(None, None).len(), 1);
+        assert_eq!(manager.list_sessions().len(), 1);
+
+        // Migration rewrites the consolidated state file and removes the legacy ones.
+        assert!(storage.path().join(STATE_FILE).exists());
+        assert!(!storage.path().join("checkpoints.json").exists());
+        assert!(!storage.path().join("sessions.json").exists());
+    }
+}
@@ -0,0 +1,170 @@
+//! Content-defined chunking and a content-addressable chunk store.
+//!
+//! Checkpoints dedupe file content against everything already captured by splitting
+//! each file into variable-length chunks at content-defined boundaries (so the same
+//! chunk recurs across checkpoints even after nearby insertions/deletions) and storing
+//! each distinct chunk once, keyed by its SHA-256 hash.
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Target average chunk size is 2^`CHUNK_MASK_BITS` bytes (8 KiB).
+const CHUNK_MASK_BITS: u32 = 13;
+const CHUNK_MASK: u64 = (1 << CHUNK_MASK_BITS) - 1;
+/// Never cut a chunk shorter than this, so small edits don't fragment every chunk.
+const MIN_CHUNK_SIZE: usize = 2 * 1024;
+/// Force a cut at this size even if the rolling hash hasn't found a boundary.
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+const CHUNKS_DIR: &str = "chunks";
+
+/// Content-addressed id of a chunk: the hex-encoded SHA-256 of its bytes.
+pub type ChunkId = String;
+
+/// 256 pseudo-random 64-bit values used to fold each input byte into the rolling hash
+/// (a Gear hash table), generated once from a fixed seed via splitmix64.
+fn gear_table() -> &'static [u64; 256] {
+    static TABLE: OnceLock<[u64; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u64; 256];
+        let mut seed: u64 = 0x9E3779B97F4A7C15;
+        for entry in table.iter_mut() {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *entry = z ^ (z >> 31);
+        }
+        table
+    })
+}
+
+/// Split `data` into content-defined chunks.
+///
+/// A Gear-style rolling hash is folded over the bytes; a boundary is declared whenever
+/// the low `CHUNK_MASK_BITS` bits are all zero, clamped to `MIN_CHUNK_SIZE` and
+/// `MAX_CHUNK_SIZE` so boundaries stay stable under edits elsewhere in the file.
+pub fn chunk_content(data: &[u8]) -> Vec<&[u8]> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    let table = gear_table();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut hash: u64 = 0;
+
+    for (i, &byte) in data.iter().enumerate() {
+        hash = (hash << 1).wrapping_add(table[byte as usize]);
+        let len = i - start + 1;
+        if len >= MAX_CHUNK_SIZE || (len >= MIN_CHUNK_SIZE && hash & CHUNK_MASK == 0) {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            hash = 0;
+        }
+    }
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+    chunks
+}
+
+/// The id of a single chunk: the hex-encoded SHA-256 digest of its bytes.
+pub fn chunk_id(chunk: &[u8]) -> ChunkId {
+    let mut hasher = Sha256::new();
+    hasher.update(chunk);
+    format!("{:x}", hasher.finalize())
+}
+
+/// A content-addressable store of chunks shared across all checkpoints.
+pub struct ChunkStore {
+    chunks_dir: PathBuf,
+}
+
+impl ChunkStore {
+    /// Open (creating if necessary) the chunk store rooted at `storage_path/chunks/`.
+    pub fn new(storage_path: &Path) -> Result<Self> {
+        let chunks_dir = storage_path.join(CHUNKS_DIR);
+        fs::create_dir_all(&chunks_dir)?;
+        Ok(Self { chunks_dir })
+    }
+
+    /// Shard chunk files by their first two hex characters so the directory doesn't
+    /// end up with tens of thousands of flat entries.
+    fn chunk_path(&self, id: &str) -> PathBuf {
+        self.chunks_dir.join(&id[0..2]).join(id)
+    }
+
+    /// Split `data` into chunks, writing any not already stored, and return the
+    /// ordered list of chunk ids needed to reconstitute it.
+    pub fn put(&self, data: &[u8]) -> Result<Vec<ChunkId>> {
+        let mut ids = Vec::new();
+        for chunk in chunk_content(data) {
+            let id = chunk_id(chunk);
+            let path = self.chunk_path(&id);
+            if !path.exists() {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, chunk)?;
+            }
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /// Total stored size (bytes) of the chunks referenced by `ids`, without reading
+    /// their content into memory.
+    pub fn size(&self, ids: &[ChunkId]) -> Result<u64> {
+        let mut total = 0u64;
+        for id in ids {
+            total += fs::metadata(self.chunk_path(id))?.len();
+        }
+        Ok(total)
+    }
+
+    /// Reassemble the original bytes from an ordered list of chunk ids.
+    pub fn get(&self, ids: &[ChunkId]) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for id in ids {
+            data.extend(fs::read(self.chunk_path(id))?);
+        }
+        Ok(data)
+    }
+
+    /// Delete every stored chunk whose id is not in `live_ids`, returning the number
+    /// of chunks removed and the total bytes freed.
+    pub fn gc(&self, live_ids: &HashSet<ChunkId>) -> Result<(usize, u64)> {
+        let mut removed = 0;
+        let mut freed_bytes = 0u64;
+
+        let Ok(shards) = fs::read_dir(&self.chunks_dir) else {
+            return Ok((0, 0));
+        };
+        for shard in shards.flatten() {
+            if !shard.path().is_dir() {
+                continue;
+            }
+            let Ok(entries) = fs::read_dir(shard.path()) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let id = entry.file_name().to_string_lossy().to_string();
+                if live_ids.contains(&id) {
+                    continue;
+                }
+                if let Ok(meta) = entry.metadata() {
+                    freed_bytes += meta.len();
+                }
+                if fs::remove_file(entry.path()).is_ok() {
+                    removed += 1;
+                }
+            }
+        }
+        Ok((removed, freed_bytes))
+    }
+}
@@ -0,0 +1,175 @@
+//! Line-level unified diff generation between a checkpoint's captured content and the
+//! current state of a file.
+
+/// A single aligned line: present only in the old text, only in the new text, or both.
+enum Op {
+    Equal { old_idx: usize, new_idx: usize },
+    Delete { old_idx: usize },
+    Insert { new_idx: usize },
+}
+
+/// Lines of context kept around each change, mirroring `diff -u`'s default.
+const CONTEXT_LINES: usize = 3;
+
+/// `lcs_table` is O(n*m) in both time and space. Above this many lines on either side,
+/// skip it and fall back to a coarse summary hunk instead of risking a multi-second
+/// stall or a table that doesn't fit in memory.
+const MAX_DIFF_LINES: usize = 2000;
+
+/// Compute unified-diff-style hunks (`@@ -l,s +l,s @@` headers followed by ` `/`-`/`+`
+/// prefixed lines) between `old` and `new`, each hunk carrying `CONTEXT_LINES` lines of
+/// surrounding, unchanged context.
+pub fn unified_diff(old: &str, new: &str) -> Vec<String> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    if old_lines.len() > MAX_DIFF_LINES || new_lines.len() > MAX_DIFF_LINES {
+        return vec![coarse_summary(&old_lines, &new_lines)];
+    }
+
+    let ops = diff_ops(&old_lines, &new_lines);
+    build_hunks(&old_lines, &new_lines, &ops)
+}
+
+/// A line-count-only summary used in place of a full LCS diff for files too large to
+/// align cheaply.
+fn coarse_summary(old_lines: &[&str], new_lines: &[&str]) -> String {
+    format!(
+        "@@ file too large to diff line-by-line ({} lines -> {} lines) @@\n",
+        old_lines.len(),
+        new_lines.len()
+    )
+}
+
+/// Longest-common-subsequence table over lines, used to align `a` and `b`.
+fn lcs_table(a: &[&str], b: &[&str]) -> Vec<Vec<u32>> {
+    let (n, m) = (a.len(), b.len());
+    let mut table = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    table
+}
+
+/// Walk the LCS table to produce the equal/delete/insert sequence that transforms `a`
+/// into `b`.
+fn diff_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let table = lcs_table(a, b);
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::new();
+
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            ops.push(Op::Equal { old_idx: i, new_idx: j });
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            ops.push(Op::Delete { old_idx: i });
+            i += 1;
+        } else {
+            ops.push(Op::Insert { new_idx: j });
+            j += 1;
+        }
+    }
+    while i < a.len() {
+        ops.push(Op::Delete { old_idx: i });
+        i += 1;
+    }
+    while j < b.len() {
+        ops.push(Op::Insert { new_idx: j });
+        j += 1;
+    }
+    ops
+}
+
+/// Group the aligned ops into hunks, keeping `CONTEXT_LINES` of unchanged lines around
+/// each change and merging runs that are close enough together to share context.
+fn build_hunks(old_lines: &[&str], new_lines: &[&str], ops: &[Op]) -> Vec<String> {
+    let mut hunks = Vec::new();
+    let mut i = 0;
+
+    while i < ops.len() {
+        if matches!(ops[i], Op::Equal { .. }) {
+            i += 1;
+            continue;
+        }
+
+        let mut start = i;
+        let mut backed = 0;
+        while start > 0 && matches!(ops[start - 1], Op::Equal { .. }) && backed < CONTEXT_LINES {
+            start -= 1;
+            backed += 1;
+        }
+
+        let mut end = i;
+        loop {
+            while end < ops.len() && !matches!(ops[end], Op::Equal { .. }) {
+                end += 1;
+            }
+            let gap_start = end;
+            while end < ops.len() && matches!(ops[end], Op::Equal { .. }) {
+                end += 1;
+            }
+            let gap_len = end - gap_start;
+            if end >= ops.len() || gap_len > CONTEXT_LINES * 2 {
+                end = (gap_start + CONTEXT_LINES.min(gap_len)).min(ops.len());
+                break;
+            }
+            // The gap is short enough to keep this run merged with whatever follows it.
+        }
+
+        hunks.push(format_hunk(old_lines, new_lines, &ops[start..end]));
+        i = end;
+    }
+
+    hunks
+}
+
+/// Render one hunk's `@@ -l,s +l,s @@` header plus its prefixed lines.
+fn format_hunk(old_lines: &[&str], new_lines: &[&str], ops: &[Op]) -> String {
+    let old_start = ops
+        .iter()
+        .find_map(|op| match op {
+            Op::Equal { old_idx, .. } | Op::Delete { old_idx } => Some(*old_idx),
+            Op::Insert { .. } => None,
+        })
+        .unwrap_or(0);
+    let new_start = ops
+        .iter()
+        .find_map(|op| match op {
+            Op::Equal { new_idx, .. } | Op::Insert { new_idx } => Some(*new_idx),
+            Op::Delete { .. } => None,
+        })
+        .unwrap_or(0);
+
+    let old_len = ops
+        .iter()
+        .filter(|op| matches!(op, Op::Equal { .. } | Op::Delete { .. }))
+        .count();
+    let new_len = ops
+        .iter()
+        .filter(|op| matches!(op, Op::Equal { .. } | Op::Insert { .. }))
+        .count();
+
+    let mut out = format!(
+        "@@ -{},{} +{},{} @@\n",
+        old_start + 1,
+        old_len,
+        new_start + 1,
+        new_len
+    );
+    for op in ops {
+        match op {
+            Op::Equal { old_idx, .. } => out.push_str(&format!(" {}\n", old_lines[*old_idx])),
+            Op::Delete { old_idx } => out.push_str(&format!("-{}\n", old_lines[*old_idx])),
+            Op::Insert { new_idx } => out.push_str(&format!("+{}\n", new_lines[*new_idx])),
+        }
+    }
+    out
+}
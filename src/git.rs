@@ -0,0 +1,150 @@
+//! In-process git integration via `git2` (libgit2), replacing shelling out to the
+//! `git` binary. Reads HEAD/branch/dirty status directly from the repository object,
+//! and can write a checkpoint's worktree content as a dangling commit object (a tree +
+//! commit that no branch or the index ever points at) so rollback can restore from it.
+
+use anyhow::Result;
+use git2::{ObjectType, Oid, Repository, Signature, StatusOptions, Tree};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// HEAD commit, current branch, and dirty/clean status, read directly from the
+/// repository object.
+pub struct GitStatus {
+    pub commit_hash: Option<String>,
+    pub branch: Option<String>,
+    pub dirty: bool,
+}
+
+/// Inspect the repository containing `worktree_path`, if any.
+pub fn status(worktree_path: &Path) -> Option<GitStatus> {
+    let repo = Repository::discover(worktree_path).ok()?;
+    let head = repo.head().ok();
+    let commit_hash = head.as_ref().and_then(|h| h.target()).map(|oid| oid.to_string());
+    let branch = head.as_ref().and_then(|h| h.shorthand()).map(str::to_string);
+    // Ignored files (build output etc.) don't make a worktree dirty.
+    let mut opts = StatusOptions::new();
+    opts.include_ignored(false).include_untracked(true);
+    let dirty = repo
+        .statuses(Some(&mut opts))
+        .map(|statuses| !statuses.is_empty())
+        .unwrap_or(false);
+    Some(GitStatus { commit_hash, branch, dirty })
+}
+
+/// Write the current content of `files` as a dangling commit object on top of HEAD (if
+/// any), without updating any ref or the index, and return the new commit's id.
+pub fn snapshot_commit(worktree_path: &Path, files: &[PathBuf]) -> Result<String> {
+    let repo = Repository::discover(worktree_path)?;
+
+    let mut blobs = Vec::with_capacity(files.len());
+    for rel_path in files {
+        let content = fs::read(worktree_path.join(rel_path))?;
+        let oid = repo.blob(&content)?;
+        blobs.push((rel_path.clone(), oid));
+    }
+
+    let tree_oid = build_tree(&repo, &blobs)?;
+    let tree = repo.find_tree(tree_oid)?;
+
+    let parent_commit = repo
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .and_then(|oid| repo.find_commit(oid).ok());
+    let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("vibe-code-guardian", "guardian@local"))?;
+
+    let commit_oid = repo.commit(
+        None, // update_ref: None, so no branch/HEAD is ever moved
+        &signature,
+        &signature,
+        "vibe-code-guardian checkpoint snapshot",
+        &tree,
+        &parents,
+    )?;
+
+    Ok(commit_oid.to_string())
+}
+
+/// Restore every file in the dangling snapshot commit `commit_hash` onto disk,
+/// returning the paths (relative to `worktree_path`) that were written.
+pub fn restore_commit(worktree_path: &Path, commit_hash: &str) -> Result<Vec<PathBuf>> {
+    let repo = Repository::discover(worktree_path)?;
+    let oid = Oid::from_str(commit_hash)?;
+    let tree = repo.find_commit(oid)?.tree()?;
+
+    let mut restored = Vec::new();
+    restore_tree(&repo, &tree, Path::new(""), worktree_path, &mut restored)?;
+    Ok(restored)
+}
+
+/// Recursively build a tree object from `entries` (relative path -> blob id), creating
+/// one sub-tree per directory component.
+fn build_tree(repo: &Repository, entries: &[(PathBuf, Oid)]) -> Result<Oid> {
+    let mut top_files: Vec<(&Path, Oid)> = Vec::new();
+    let mut subdirs: BTreeMap<&OsStr, Vec<(PathBuf, Oid)>> = BTreeMap::new();
+
+    for (path, oid) in entries {
+        let mut components = path.components();
+        let first = components
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("empty path in checkpoint file list"))?;
+        let rest: PathBuf = components.collect();
+        if rest.as_os_str().is_empty() {
+            top_files.push((path.as_path(), *oid));
+        } else {
+            subdirs.entry(first.as_os_str()).or_default().push((rest, *oid));
+        }
+    }
+
+    let mut builder = repo.treebuilder(None)?;
+    for (path, oid) in top_files {
+        let name = path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("path has no file name: {}", path.display()))?;
+        builder.insert(name.to_string_lossy().as_ref(), oid, 0o100644)?;
+    }
+    for (dir_name, child_entries) in subdirs {
+        let child_tree_oid = build_tree(repo, &child_entries)?;
+        builder.insert(dir_name.to_string_lossy().as_ref(), child_tree_oid, 0o040000)?;
+    }
+    Ok(builder.write()?)
+}
+
+/// Recursively write every blob in `tree` to disk under `worktree_path`, mirroring the
+/// tree's directory structure starting at `prefix`.
+fn restore_tree(
+    repo: &Repository,
+    tree: &Tree,
+    prefix: &Path,
+    worktree_path: &Path,
+    restored: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in tree.iter() {
+        let name = entry.name().unwrap_or_default();
+        let rel_path = prefix.join(name);
+        match entry.kind() {
+            Some(ObjectType::Blob) => {
+                let blob = repo.find_blob(entry.id())?;
+                let full_path = worktree_path.join(&rel_path);
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&full_path, blob.content())?;
+                restored.push(rel_path);
+            }
+            Some(ObjectType::Tree) => {
+                let subtree = repo.find_tree(entry.id())?;
+                restore_tree(repo, &subtree, &rel_path, worktree_path, restored)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
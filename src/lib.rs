@@ -1,8 +1,11 @@
 mod checkpoint;
+mod chunk;
+mod diff;
+mod git;
 mod types;
 
 use checkpoint::CheckpointManager;
-use types::Checkpoint;
+use types::{Checkpoint, CheckpointSort, DiffType, PruneScope};
 use zed_extension_api::{
     self as zed,
     Command, Context, SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput,
@@ -32,6 +35,16 @@ impl VibeGuardianExtension {
     }
 }
 
+/// Parse a `CheckpointSort` from a "prune-checkpoints" argument.
+fn parse_sort(arg: &str) -> Option<CheckpointSort> {
+    match arg.to_lowercase().as_str() {
+        "oldest" => Some(CheckpointSort::Oldest),
+        "largest" => Some(CheckpointSort::Largest),
+        "alpha" => Some(CheckpointSort::Alpha),
+        _ => None,
+    }
+}
+
 impl zed::Extension for VibeGuardianExtension {
     fn new() -> Self {
         Self::new()
@@ -57,6 +70,15 @@ impl zed::Extension for VibeGuardianExtension {
 
         let manager = self.get_manager(worktree)?;
 
+        // A slash-command invocation is the only signal this extension gets that the
+        // user is actively working in the worktree (there's no dedicated file-change
+        // event), so treat each one as an observed change and give the debounced
+        // cadence policy a chance to fire before handling the command itself.
+        manager.record_change();
+        manager
+            .maybe_auto_checkpoint(worktree.path())
+            .map_err(|e| format!("Failed to run auto-checkpoint: {}", e))?;
+
         match command.name.as_str() {
             "create-checkpoint" => {
                 let name = args.first()
@@ -93,28 +115,62 @@ impl zed::Extension for VibeGuardianExtension {
             "view-diff" => {
                 let checkpoint_id = args.first()
                     .ok_or("Please specify a checkpoint ID")?;
-                Ok(SlashCommandOutput {
-                    sections: vec![],
-                    text: format!("Diff view for: {}", checkpoint_id),
-                })
+                match manager.diff_checkpoint(checkpoint_id, worktree.path()) {
+                    Ok(diff) => {
+                        let added = diff.files.iter().filter(|f| matches!(f.diff_type, DiffType::Added)).count();
+                        let modified = diff.files.iter().filter(|f| matches!(f.diff_type, DiffType::Modified)).count();
+                        let deleted = diff.files.iter().filter(|f| matches!(f.diff_type, DiffType::Deleted)).count();
+
+                        let mut text = format!(
+                            "Diff for {} ({} added, {} modified, {} deleted):\n\n",
+                            checkpoint_id, added, modified, deleted
+                        );
+                        let mut sections = Vec::new();
+
+                        for file in diff.files.iter().filter(|f| !matches!(f.diff_type, DiffType::Unchanged)) {
+                            let start = text.len() as u32;
+                            text.push_str(&format!("--- {} ({:?})\n", file.path, file.diff_type));
+                            for hunk in &file.hunks {
+                                text.push_str(hunk);
+                            }
+                            text.push('\n');
+                            let end = text.len() as u32;
+                            sections.push(SlashCommandOutputSection {
+                                range: start..end,
+                                label: format!("{} ({:?})", file.path, file.diff_type),
+                            });
+                        }
+
+                        Ok(SlashCommandOutput { sections, text })
+                    }
+                    Err(e) => Err(format!("Failed to diff checkpoint: {}", e)),
+                }
             }
             "list-checkpoints" => {
                 let session_id = args.first().map(|s| s.as_str());
-                let checkpoints = manager.list_checkpoints(session_id);
+                let branch = args.get(1).map(|s| s.as_str());
+                let checkpoints = manager.list_checkpoints(session_id, branch);
                 if checkpoints.is_empty() {
                     Ok(SlashCommandOutput {
                         sections: vec![],
                         text: "No checkpoints found".to_string(),
                     })
                 } else {
+                    let mut by_branch: std::collections::BTreeMap<String, Vec<&Checkpoint>> =
+                        std::collections::BTreeMap::new();
+                    for cp in &checkpoints {
+                        by_branch
+                            .entry(cp.branch.clone().unwrap_or_else(|| "(no branch)".to_string()))
+                            .or_default()
+                            .push(cp);
+                    }
+
                     let mut text = format!("Checkpoints ({}):\n", checkpoints.len());
-                    for (i, cp) in checkpoints.iter().enumerate() {
-                        text.push_str(&format!(
-                            "  {}. {} - {} ({})\n",
-                            i + 1,
-                            cp.name,
-                            cp.id
-                        ));
+                    for (branch_name, cps) in by_branch {
+                        text.push_str(&format!("\n{}:\n", branch_name));
+                        for (i, cp) in cps.iter().enumerate() {
+                            text.push_str(&format!("  {}. {} - {}\n", i + 1, cp.name, cp.id));
+                        }
                     }
                     Ok(SlashCommandOutput {
                         sections: vec![],
@@ -122,6 +178,36 @@ impl zed::Extension for VibeGuardianExtension {
                     })
                 }
             }
+            "prune-checkpoints" => {
+                let scope = match args.first().map(|s| s.as_str()) {
+                    Some("all") => PruneScope::All,
+                    Some(sort_arg) => {
+                        let sort = parse_sort(sort_arg).ok_or_else(|| {
+                            format!("Unknown sort: {} (expected oldest, largest, alpha, or all)", sort_arg)
+                        })?;
+                        let n: u32 = args
+                            .get(1)
+                            .ok_or("Please specify how many checkpoints to prune")?
+                            .parse()
+                            .map_err(|_| "Checkpoint count must be a number".to_string())?;
+                        let invert = args.get(2).map(|s| s == "invert").unwrap_or(false);
+                        PruneScope::Group { sort, invert, n }
+                    }
+                    None => {
+                        return Err(
+                            "Please specify a sort (oldest, largest, alpha) and count, or 'all'"
+                                .to_string(),
+                        )
+                    }
+                };
+                match manager.prune_checkpoints(None, scope) {
+                    Ok((removed, freed_bytes)) => Ok(SlashCommandOutput {
+                        sections: vec![],
+                        text: format!("Pruned {} checkpoint(s), freed {} bytes", removed, freed_bytes),
+                    }),
+                    Err(e) => Err(format!("Failed to prune checkpoints: {}", e)),
+                }
+            }
             "delete-checkpoint" => {
                 let checkpoint_id = args.first()
                     .ok_or("Please specify a checkpoint ID")?;
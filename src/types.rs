@@ -9,12 +9,22 @@ pub struct Checkpoint {
     pub name: String,
     /// When the checkpoint was created
     pub timestamp: i64,
-    /// Git commit hash (if available)
+    /// Git HEAD commit hash at checkpoint time (informational only; never used to
+    /// restore content, since it may predate uncommitted changes the checkpoint captured)
     pub commit_hash: Option<String>,
     /// Session ID this checkpoint belongs to
     pub session_id: String,
     /// Number of files tracked in this checkpoint
     pub file_count: usize,
+    /// Git branch checked out at checkpoint time (if available)
+    pub branch: Option<String>,
+    /// Id of the dangling git commit object holding this checkpoint's own content, if
+    /// the git-backed snapshot strategy was enabled and succeeded. Only this field is
+    /// restorable by `rollback`; `commit_hash` alone is not.
+    ///
+    /// `serde(default)` so checkpoints persisted before this field existed keep loading.
+    #[serde(default)]
+    pub git_snapshot_commit: Option<String>,
 }
 
 /// Represents a coding session
@@ -30,6 +40,17 @@ pub struct Session {
     pub end_time: Option<i64>,
 }
 
+/// Governs when `CheckpointManager::maybe_auto_checkpoint` is willing to act.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CheckpointMode {
+    /// Never create automatic checkpoints; only explicit commands do.
+    Never,
+    /// Create an automatic checkpoint after every N observed file changes.
+    Every(u32),
+    /// Create an automatic checkpoint on every observed file change.
+    Always,
+}
+
 /// Configuration for the guardian
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuardianConfig {
@@ -41,6 +62,22 @@ pub struct GuardianConfig {
     pub max_checkpoints_per_session: u32,
     /// Enable checkpoint on AI changes
     pub auto_checkpoint_on_ai_changes: bool,
+    /// Cadence policy for automatic checkpoints
+    pub checkpoint_mode: CheckpointMode,
+    /// Never create an automatic checkpoint sooner than this many seconds since the last one
+    pub min_checkpoint_interval_secs: u64,
+    /// Never create an automatic checkpoint until at least this many file changes accumulated
+    pub min_checkpoint_ops: u32,
+    /// Checkpoints timestamped before this (ms since epoch) are dropped on load instead of
+    /// accumulating forever. `None` keeps everything.
+    pub ignore_before: Option<i64>,
+    /// Extra gitignore-style glob patterns excluded from tracking, on top of whatever
+    /// `.gitignore`/`.ignore` already exclude
+    pub extra_ignore_patterns: Vec<String>,
+    /// When the worktree has a git dir, capture checkpoints as dangling git commit
+    /// objects (instead of only the chunk-store manifest) so rollback can restore
+    /// straight from the repository
+    pub git_snapshot_enabled: bool,
 }
 
 impl Default for GuardianConfig {
@@ -50,6 +87,12 @@ impl Default for GuardianConfig {
             auto_save_interval_minutes: 5,
             max_checkpoints_per_session: 50,
             auto_checkpoint_on_ai_changes: true,
+            checkpoint_mode: CheckpointMode::Always,
+            min_checkpoint_interval_secs: 60,
+            min_checkpoint_ops: 3,
+            ignore_before: None,
+            extra_ignore_patterns: vec!["target".to_string()],
+            git_snapshot_enabled: true,
         }
     }
 }
@@ -73,3 +116,63 @@ pub enum ChangeType {
     Modified,
     Deleted,
 }
+
+/// Classification of how a tracked file differs from its checkpointed content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DiffType {
+    Added,
+    Modified,
+    Deleted,
+    Unchanged,
+}
+
+/// A single file's diff against a checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// File path, relative to the worktree root
+    pub path: String,
+    pub diff_type: DiffType,
+    /// Unified-diff-style hunks; empty for `Unchanged` files
+    pub hunks: Vec<String>,
+}
+
+/// The result of diffing a checkpoint's captured content against the current worktree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointDiff {
+    pub checkpoint_id: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// Sort order used when selecting checkpoints to prune.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CheckpointSort {
+    /// By creation time, oldest first
+    Oldest,
+    /// By total captured content size, largest first
+    Largest,
+    /// By name, alphabetically
+    Alpha,
+}
+
+/// Which checkpoints a prune operation should target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PruneScope {
+    /// Every checkpoint in scope
+    All,
+    /// The first `n` checkpoints in `sort` order (or the last `n` if `invert`)
+    Group {
+        sort: CheckpointSort,
+        invert: bool,
+        n: u32,
+    },
+}
+
+/// A tracked file's content at checkpoint time, represented as the ordered list of
+/// content-addressed chunks needed to reconstitute it from the chunk store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileManifestEntry {
+    /// File path, relative to the worktree root
+    pub path: String,
+    /// Ordered chunk ids that reconstitute the file's content
+    pub chunk_ids: Vec<String>,
+}